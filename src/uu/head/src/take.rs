@@ -1,7 +1,10 @@
 //! Take all but the last elements of an iterator or sequential reader.
-use std::io::Read;
+use std::io::{BufRead, Read};
 use uucore::ringbuffer::RingBuffer;
 
+/// Block size used when refilling the internal buffer for [`BufRead`].
+const FILL_BUF_SIZE: usize = 8192;
+
 /// Create an iterator over all but the last `n` elements of `iter`.
 ///
 /// # Examples
@@ -83,6 +86,14 @@ pub fn read_all_but<R: Read>(reader: R, n: usize) -> ReadAllBut<R> {
 pub struct ReadAllBut<R> {
     reader: R,
     ring_buffer: RingBuffer<u8>,
+    /// Number of trailing bytes to hold back.
+    n: usize,
+    /// Reusable scratch space for each `read` call, to avoid per-call allocs.
+    scratch: Vec<u8>,
+    /// Emittable bytes produced for [`BufRead::fill_buf`] but not yet consumed.
+    fill: Vec<u8>,
+    /// Offset of the first unconsumed byte in `fill`.
+    pos: usize,
 }
 
 impl<R: Read> ReadAllBut<R> {
@@ -100,29 +111,336 @@ impl<R: Read> ReadAllBut<R> {
         ReadAllBut {
             reader,
             ring_buffer,
+            n,
+            scratch: vec![],
+            fill: vec![],
+            pos: 0,
         }
     }
+
+    /// Consume the adaptor, returning the underlying reader.
+    ///
+    /// The last `n` bytes still held in the ring buffer are discarded; the
+    /// returned reader continues from where this adaptor stopped reading.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
 }
 
 impl<R: Read> Read for ReadAllBut<R> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let mut tmp = vec![0u8; buf.len()];
-        match self.reader.read(&mut tmp) {
-            Ok(m) => {
-                let mut i = 0;
-                for b in tmp[0..m].iter() {
-                    if let Some(out_byte) = self.ring_buffer.push_back(*b) {
-                        buf[i] = out_byte;
-                        i += 1;
+        // Read a fresh block into the reusable scratch buffer.
+        self.scratch.resize(buf.len(), 0);
+        let m = self.reader.read(&mut self.scratch)?;
+
+        // Of the `buffered + m` bytes now known to us, all but the last `n`
+        // may be emitted, but at most `m` per call.
+        let buffered = self.ring_buffer.len();
+        let emit = m.min((buffered + m).saturating_sub(self.n));
+
+        // The emitted bytes come first from the ring buffer's existing
+        // contents, then from the front of the freshly read slice.
+        let from_ring = emit.min(buffered);
+        let from_scratch = emit - from_ring;
+
+        // Copy the ring-buffer prefix in bulk across the wrap point.
+        let (a, b) = self.ring_buffer.as_slices();
+        let take_a = from_ring.min(a.len());
+        buf[..take_a].copy_from_slice(&a[..take_a]);
+        buf[take_a..from_ring].copy_from_slice(&b[..from_ring - take_a]);
+        self.ring_buffer.drain_front(from_ring);
+
+        // Copy the prefix of the fresh slice that is allowed to leave.
+        buf[from_ring..emit].copy_from_slice(&self.scratch[..from_scratch]);
+
+        // Retain the remaining fresh bytes as the new held-back tail.
+        self.ring_buffer
+            .extend_from_slice(&self.scratch[from_scratch..m]);
+
+        Ok(emit)
+    }
+}
+
+impl<R: Read> BufRead for ReadAllBut<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        // Refill only once the previously buffered bytes are all consumed.
+        if self.pos >= self.fill.len() {
+            self.fill.clear();
+            self.pos = 0;
+
+            // Keep reading fresh blocks until some bytes become emittable or
+            // the underlying reader reaches EOF. The guaranteed-unemittable
+            // last `n` bytes stay held in the ring buffer across fills.
+            self.scratch.resize(FILL_BUF_SIZE, 0);
+            loop {
+                let m = self.reader.read(&mut self.scratch)?;
+                if m == 0 {
+                    break;
+                }
+
+                let buffered = self.ring_buffer.len();
+                let emit = (buffered + m).saturating_sub(self.n);
+                let from_ring = emit.min(buffered);
+                let from_scratch = emit - from_ring;
+
+                let (a, b) = self.ring_buffer.as_slices();
+                let take_a = from_ring.min(a.len());
+                self.fill.extend_from_slice(&a[..take_a]);
+                self.fill.extend_from_slice(&b[..from_ring - take_a]);
+                self.ring_buffer.drain_front(from_ring);
+
+                self.fill.extend_from_slice(&self.scratch[..from_scratch]);
+                self.ring_buffer
+                    .extend_from_slice(&self.scratch[from_scratch..m]);
+
+                if !self.fill.is_empty() {
+                    break;
+                }
+            }
+        }
+        Ok(&self.fill[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.fill.len());
+    }
+}
+
+/// Create an iterator over the first `n` elements of `iter`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let data = [1, 2, 3, 4, 5];
+/// let n = 2;
+/// let mut iter = take_first(data.iter(), n);
+/// assert_eq!(Some(&1), iter.next());
+/// assert_eq!(Some(&2), iter.next());
+/// assert_eq!(None, iter.next());
+/// ```
+pub fn take_first<I: Iterator>(iter: I, n: usize) -> TakeFirst<I> {
+    TakeFirst { iter, remaining: n }
+}
+
+/// An iterator that yields only the first elements of another iterator.
+pub struct TakeFirst<I: Iterator> {
+    iter: I,
+    remaining: usize,
+}
+
+impl<I: Iterator> Iterator for TakeFirst<I> {
+    type Item = <I as Iterator>::Item;
+
+    fn next(&mut self) -> Option<<I as Iterator>::Item> {
+        if self.remaining == 0 {
+            None
+        } else {
+            self.remaining -= 1;
+            self.iter.next()
+        }
+    }
+}
+
+/// Return an adaptor that reads only the first `n` bytes from a reader.
+///
+/// This function returns a new instance of [`Read`] that reads the first
+/// `n` bytes, after which it will always return [`Ok`](0), representing
+/// the end of the file (EOF).
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::io::Cursor;
+///
+/// let mut reader = read_first(Cursor::new(b"vwxyz"), 2);
+/// let mut buf = vec![];
+/// reader.read_to_end(&mut buf).unwrap();
+/// assert_eq!(buf, b"vw");
+/// ```
+pub fn read_first<R: Read>(reader: R, n: usize) -> ReadFirst<R> {
+    ReadFirst { reader, remaining: n }
+}
+
+/// A reader adaptor that reads only the first bytes from a given reader.
+pub struct ReadFirst<R> {
+    reader: R,
+    remaining: usize,
+}
+
+impl<R: Read> ReadFirst<R> {
+    /// Consume the adaptor, returning the underlying reader.
+    ///
+    /// The returned reader continues from where this adaptor stopped reading.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Read for ReadFirst<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.remaining == 0 {
+            return Ok(0);
+        }
+        let limit = buf.len().min(self.remaining);
+        let m = self.reader.read(&mut buf[..limit])?;
+        self.remaining -= m;
+        Ok(m)
+    }
+}
+
+/// Create an iterator over all but the last `n` records of `iter`.
+///
+/// Records are separated by `delimiter` and each yielded record includes
+/// its trailing delimiter. A final record with no trailing delimiter is
+/// treated as a complete record. This generalizes [`take_all_but`] from
+/// single bytes to whole `delimiter`-separated records, which is what
+/// `head -n -N` (lines) and `head -z -n -N` (NUL records) need.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let data = *b"a\nb\nc\n";
+/// let mut iter = take_all_but_records(data.iter().copied(), 1, b'\n');
+/// assert_eq!(iter.collect::<Vec<u8>>(), b"a\nb\n");
+/// ```
+pub fn take_all_but_records<I: Iterator<Item = u8>>(
+    iter: I,
+    n: usize,
+    delimiter: u8,
+) -> TakeAllButRecords<I> {
+    TakeAllButRecords {
+        iter,
+        buf: RingBuffer::new(n),
+        delimiter,
+        current: Vec::new(),
+        out: std::collections::VecDeque::new(),
+        done: false,
+    }
+}
+
+/// An iterator over all but the last `n` `delimiter`-separated records.
+pub struct TakeAllButRecords<I> {
+    iter: I,
+    buf: RingBuffer<Vec<u8>>,
+    delimiter: u8,
+    current: Vec<u8>,
+    out: std::collections::VecDeque<u8>,
+    done: bool,
+}
+
+impl<I: Iterator<Item = u8>> TakeAllButRecords<I> {
+    /// Push a complete record into the ring buffer, queuing any evicted one.
+    fn finish_record(&mut self) {
+        let record = std::mem::take(&mut self.current);
+        if let Some(evicted) = self.buf.push_back(record) {
+            self.out.extend(evicted);
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for TakeAllButRecords<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        loop {
+            if let Some(byte) = self.out.pop_front() {
+                return Some(byte);
+            }
+            if self.done {
+                return None;
+            }
+            match self.iter.next() {
+                Some(byte) => {
+                    self.current.push(byte);
+                    if byte == self.delimiter {
+                        self.finish_record();
+                    }
+                }
+                None => {
+                    // Flush a trailing record that had no delimiter, then
+                    // hold back the last `n` records by never emitting them.
+                    if !self.current.is_empty() {
+                        self.finish_record();
                     }
+                    self.done = true;
                 }
-                Ok(i)
             }
-            Err(e) => Err(e),
         }
     }
 }
 
+/// Return an adaptor that reads all but the last `n` records from a reader.
+///
+/// Records are separated by `delimiter` and each emitted record keeps its
+/// trailing delimiter; see [`take_all_but_records`] for the semantics.
+pub fn read_all_but_records<R: Read>(
+    reader: R,
+    n: usize,
+    delimiter: u8,
+) -> ReadAllButRecords<R> {
+    ReadAllButRecords {
+        reader,
+        buf: RingBuffer::new(n),
+        delimiter,
+        current: Vec::new(),
+        out: std::collections::VecDeque::new(),
+        scratch: vec![],
+        eof: false,
+    }
+}
+
+/// A reader adaptor over all but the last `n` `delimiter`-separated records.
+pub struct ReadAllButRecords<R> {
+    reader: R,
+    buf: RingBuffer<Vec<u8>>,
+    delimiter: u8,
+    current: Vec<u8>,
+    out: std::collections::VecDeque<u8>,
+    scratch: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: Read> ReadAllButRecords<R> {
+    fn finish_record(&mut self) {
+        let record = std::mem::take(&mut self.current);
+        if let Some(evicted) = self.buf.push_back(record) {
+            self.out.extend(evicted);
+        }
+    }
+}
+
+impl<R: Read> Read for ReadAllButRecords<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.out.is_empty() && !self.eof {
+            self.scratch.resize(FILL_BUF_SIZE, 0);
+            let m = self.reader.read(&mut self.scratch)?;
+            if m == 0 {
+                if !self.current.is_empty() {
+                    self.finish_record();
+                }
+                self.eof = true;
+                break;
+            }
+            // Move the block out so we can iterate it while mutating `self`,
+            // then hand the allocation back for reuse next round.
+            let block = std::mem::take(&mut self.scratch);
+            for &byte in &block[..m] {
+                self.current.push(byte);
+                if byte == self.delimiter {
+                    self.finish_record();
+                }
+            }
+            self.scratch = block;
+        }
+
+        let amount = buf.len().min(self.out.len());
+        for slot in buf.iter_mut().take(amount) {
+            *slot = self.out.pop_front().unwrap();
+        }
+        Ok(amount)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -159,6 +477,123 @@ mod tests {
         }
     }
 
+    mod take_all_but_records {
+
+        use crate::take::take_all_but_records;
+
+        fn collect(data: &[u8], n: usize, delimiter: u8) -> Vec<u8> {
+            take_all_but_records(data.iter().copied(), n, delimiter).collect()
+        }
+
+        #[test]
+        fn test_hold_back_one() {
+            assert_eq!(collect(b"a\nb\nc\n", 1, b'\n'), b"a\nb\n");
+        }
+
+        #[test]
+        fn test_hold_back_zero() {
+            assert_eq!(collect(b"a\nb\nc\n", 0, b'\n'), b"a\nb\nc\n");
+        }
+
+        #[test]
+        fn test_no_trailing_delimiter() {
+            assert_eq!(collect(b"a\nb\nc", 1, b'\n'), b"a\nb\n");
+        }
+
+        #[test]
+        fn test_fewer_records_than_held() {
+            assert_eq!(collect(b"a\nb\n", 3, b'\n'), b"");
+        }
+
+        #[test]
+        fn test_nul_delimiter() {
+            assert_eq!(collect(b"a\0b\0c\0", 1, b'\0'), b"a\0b\0");
+        }
+    }
+
+    mod read_all_but_records {
+
+        use crate::take::read_all_but_records;
+        use std::io::{Cursor, Read};
+
+        fn collect(data: &[u8], n: usize, delimiter: u8) -> Vec<u8> {
+            let mut reader = read_all_but_records(Cursor::new(data.to_vec()), n, delimiter);
+            let mut buf = vec![];
+            reader.read_to_end(&mut buf).unwrap();
+            buf
+        }
+
+        #[test]
+        fn test_hold_back_one() {
+            assert_eq!(collect(b"a\nb\nc\n", 1, b'\n'), b"a\nb\n");
+        }
+
+        #[test]
+        fn test_no_trailing_delimiter() {
+            assert_eq!(collect(b"a\nb\nc", 1, b'\n'), b"a\nb\n");
+        }
+    }
+
+    mod take_first {
+
+        use crate::take::take_first;
+
+        #[test]
+        fn test_fewer_elements() {
+            let mut iter = take_first([0, 1].iter(), 3);
+            assert_eq!(Some(&0), iter.next());
+            assert_eq!(Some(&1), iter.next());
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn test_more_elements() {
+            let mut iter = take_first([0, 1, 2].iter(), 2);
+            assert_eq!(Some(&0), iter.next());
+            assert_eq!(Some(&1), iter.next());
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn test_zero_elements() {
+            let mut iter = take_first([0, 1, 2].iter(), 0);
+            assert_eq!(None, iter.next());
+        }
+    }
+
+    mod read_first {
+
+        use crate::take::read_first;
+        use std::io::{Cursor, Read};
+
+        #[test]
+        fn test_fewer_bytes() {
+            let mut reader = read_first(Cursor::new(b"xy"), 3);
+            let mut buf = vec![];
+            reader.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, b"xy");
+        }
+
+        #[test]
+        fn test_more_bytes() {
+            let mut reader = read_first(Cursor::new(b"xyz"), 2);
+            let mut buf = vec![];
+            reader.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, b"xy");
+        }
+
+        #[test]
+        fn test_into_inner() {
+            let mut reader = read_first(Cursor::new(b"xyz"), 2);
+            let mut buf = vec![];
+            reader.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, b"xy");
+            let mut rest = vec![];
+            reader.into_inner().read_to_end(&mut rest).unwrap();
+            assert_eq!(rest, b"z");
+        }
+    }
+
     mod read_all_but {
 
         use crate::take::read_all_but;
@@ -195,5 +630,13 @@ mod tests {
             reader.read_to_end(&mut buf).unwrap();
             assert_eq!(buf, b"xyz");
         }
+
+        #[test]
+        fn test_bufread_lines() {
+            use std::io::BufRead;
+            let reader = read_all_but(Cursor::new(b"alpha\nbeta\ngamma\n"), 1);
+            let lines: Vec<String> = reader.lines().map(Result::unwrap).collect();
+            assert_eq!(lines, vec!["alpha", "beta", "gamma"]);
+        }
     }
 }
@@ -0,0 +1,291 @@
+//  * This file is part of the uutils coreutils package.
+//  *
+//  * (c) 2014 Vsevolod Velichko <torkvemada@sorokdva.net>
+//  *
+//  * For the full copyright and license information, please view the LICENSE
+//  * file that was distributed with this source code.
+
+// spell-checker:ignore (ToDO) retcode
+
+#[macro_use]
+extern crate uucore;
+
+use clap::{App, Arg};
+use std::io::{stdout, Write};
+use std::path::{Component, Path, PathBuf};
+
+use uucore::fs::{canonicalize, CanonicalizeMode};
+use uucore::InvalidEncodingHandling;
+
+static VERSION: &str = env!("CARGO_PKG_VERSION");
+static ABOUT: &str = "print the resolved path";
+
+static OPT_QUIET: &str = "quiet";
+static OPT_STRIP: &str = "strip";
+static OPT_ZERO: &str = "zero";
+static OPT_PHYSICAL: &str = "physical";
+static OPT_LOGICAL: &str = "logical";
+static OPT_CANONICALIZE_EXISTING: &str = "canonicalize-existing";
+static OPT_CANONICALIZE_MISSING: &str = "canonicalize-missing";
+static OPT_RELATIVE_TO: &str = "relative-to";
+static OPT_RELATIVE_BASE: &str = "relative-base";
+
+static ARG_FILES: &str = "files";
+
+fn get_usage() -> String {
+    format!("{} [OPTION]... FILE...", executable!())
+}
+
+pub fn uumain(args: impl uucore::Args) -> i32 {
+    let usage = get_usage();
+    let args = args
+        .collect_str(InvalidEncodingHandling::ConvertLossy)
+        .accept_any();
+
+    let matches = App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .usage(&usage[..])
+        .arg(
+            Arg::with_name(OPT_QUIET)
+                .short("q")
+                .long(OPT_QUIET)
+                .help("suppress most error messages"),
+        )
+        .arg(
+            Arg::with_name(OPT_CANONICALIZE_EXISTING)
+                .short("e")
+                .long(OPT_CANONICALIZE_EXISTING)
+                .help("all components of the path must exist"),
+        )
+        .arg(
+            Arg::with_name(OPT_CANONICALIZE_MISSING)
+                .short("m")
+                .long(OPT_CANONICALIZE_MISSING)
+                .help("no path components need exist or be a directory"),
+        )
+        .arg(
+            Arg::with_name(OPT_STRIP)
+                .short("s")
+                .long(OPT_STRIP)
+                .help("do not expand symbolic links"),
+        )
+        .arg(
+            Arg::with_name(OPT_ZERO)
+                .short("z")
+                .long(OPT_ZERO)
+                .help("separate output with NUL rather than newline"),
+        )
+        .arg(
+            Arg::with_name(OPT_LOGICAL)
+                .short("L")
+                .long(OPT_LOGICAL)
+                .help("resolve '..' components before symlinks"),
+        )
+        .arg(
+            Arg::with_name(OPT_PHYSICAL)
+                .short("P")
+                .long(OPT_PHYSICAL)
+                .help("resolve symlinks as encountered (default)"),
+        )
+        .arg(
+            Arg::with_name(OPT_RELATIVE_TO)
+                .long(OPT_RELATIVE_TO)
+                .takes_value(true)
+                .value_name("DIR")
+                .help("print the resolved path relative to DIR"),
+        )
+        .arg(
+            Arg::with_name(OPT_RELATIVE_BASE)
+                .long(OPT_RELATIVE_BASE)
+                .takes_value(true)
+                .value_name("DIR")
+                .help("print absolute paths unless paths below DIR"),
+        )
+        .arg(Arg::with_name(ARG_FILES).multiple(true).required(true))
+        .get_matches_from(args);
+
+    let strip = matches.is_present(OPT_STRIP);
+    let zero = matches.is_present(OPT_ZERO);
+    let quiet = matches.is_present(OPT_QUIET);
+    let can_mode = if matches.is_present(OPT_CANONICALIZE_EXISTING) {
+        CanonicalizeMode::Existing
+    } else if matches.is_present(OPT_CANONICALIZE_MISSING) {
+        CanonicalizeMode::Missing
+    } else {
+        CanonicalizeMode::Normal
+    };
+    // `-P` is the default; the later of `-L`/`-P` on the command line wins,
+    // so compare the positions at which each flag last occurred.
+    let last_index = |name| matches.indices_of(name).and_then(|i| i.last());
+    let logical = match (last_index(OPT_LOGICAL), last_index(OPT_PHYSICAL)) {
+        (Some(l), Some(p)) => l > p,
+        (Some(_), None) => true,
+        _ => false,
+    };
+
+    let relative_to = matches.value_of(OPT_RELATIVE_TO).map(resolve_dir);
+    let relative_base = matches.value_of(OPT_RELATIVE_BASE).map(resolve_dir);
+
+    let files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    let mut retcode = 0;
+    for file in &files {
+        let path = Path::new(file);
+        match resolve(path, strip, logical, can_mode) {
+            Ok(resolved) => {
+                let output =
+                    relativize(&resolved, relative_to.as_deref(), relative_base.as_deref());
+                print_path(&output, zero);
+            }
+            Err(e) => {
+                if !quiet {
+                    show_error!("{}: {}", path.display(), e);
+                }
+                retcode = 1;
+            }
+        }
+    }
+    retcode
+}
+
+/// Write `path` to stdout, terminated by NUL when `zero` is set.
+fn print_path(path: &Path, zero: bool) {
+    let separator = if zero { b"\0" } else { b"\n" };
+    let stdout = stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(path.to_string_lossy().as_bytes()).unwrap();
+    handle.write_all(separator).unwrap();
+}
+
+/// Resolve a directory operand of `--relative-to`/`--relative-base`, ignoring
+/// errors (an unresolvable base simply never matches).
+fn resolve_dir(dir: &str) -> PathBuf {
+    resolve(Path::new(dir), false, false, CanonicalizeMode::Normal)
+        .unwrap_or_else(|_| PathBuf::from(dir))
+}
+
+/// Canonicalize `path` to an absolute path according to the symlink policy.
+fn resolve(
+    path: &Path,
+    strip: bool,
+    logical: bool,
+    can_mode: CanonicalizeMode,
+) -> std::io::Result<PathBuf> {
+    let absolute = absolutize(path);
+    if strip {
+        // `-s` skips symlink expansion, but the strictness requested by
+        // `-e`/`-m` must still be honored rather than silently ignored.
+        let normalized = normalize_lexically(&absolute);
+        check_existence(&normalized, can_mode)?;
+        Ok(normalized)
+    } else if logical {
+        canonicalize(normalize_lexically(&absolute), can_mode)
+    } else {
+        canonicalize(absolute, can_mode)
+    }
+}
+
+/// Enforce the `-e`/`-m`/default existence requirement without following
+/// symlinks (used by the `-s` strip path, which never resolves links).
+fn check_existence(path: &Path, can_mode: CanonicalizeMode) -> std::io::Result<()> {
+    use std::io::{Error, ErrorKind};
+
+    let missing = |p: &Path| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!("{}: No such file or directory", p.display()),
+        )
+    };
+
+    match can_mode {
+        // Every component must exist.
+        CanonicalizeMode::Existing => {
+            if path.symlink_metadata().is_err() {
+                return Err(missing(path));
+            }
+        }
+        // No component needs to exist.
+        CanonicalizeMode::Missing => {}
+        // The default: all but the last component must exist.
+        _ => {
+            if let Some(parent) = path.parent() {
+                if !parent.as_os_str().is_empty() && parent.symlink_metadata().is_err() {
+                    return Err(missing(parent));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Join `path` with the current directory when it is relative.
+fn absolutize(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        let mut cwd = std::env::current_dir().unwrap_or_default();
+        cwd.push(path);
+        cwd
+    }
+}
+
+/// Resolve `.` and `..` components textually, without touching the filesystem.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                if result.file_name().is_some() {
+                    result.pop();
+                } else if !result.has_root() {
+                    result.push("..");
+                }
+            }
+            Component::CurDir => {}
+            other => result.push(other.as_os_str()),
+        }
+    }
+    result
+}
+
+/// Apply the `--relative-to`/`--relative-base` policy to a resolved path.
+fn relativize(path: &Path, relative_to: Option<&Path>, relative_base: Option<&Path>) -> PathBuf {
+    // `--relative-base` gates whether any relativization happens at all.
+    if let Some(base) = relative_base {
+        if !path.starts_with(base) {
+            return path.to_path_buf();
+        }
+    }
+    match relative_to.or(relative_base) {
+        Some(dir) => make_relative(path, dir),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Express `path` relative to `dir`, component by component.
+fn make_relative(path: &Path, dir: &Path) -> PathBuf {
+    let path_components: Vec<Component> = path.components().collect();
+    let dir_components: Vec<Component> = dir.components().collect();
+
+    let common = path_components
+        .iter()
+        .zip(dir_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = PathBuf::new();
+    for _ in common..dir_components.len() {
+        result.push("..");
+    }
+    for component in &path_components[common..] {
+        result.push(component.as_os_str());
+    }
+    if result.as_os_str().is_empty() {
+        result.push(".");
+    }
+    result
+}
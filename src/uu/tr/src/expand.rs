@@ -0,0 +1,302 @@
+//  * This file is part of the uutils coreutils package.
+//  *
+//  * (c) kwantam <kwantam@gmail.com>
+//  *     * 2015-04-28 ~ created `expand` module to eliminate most allocs during setup
+//  *
+//  * For the full copyright and license information, please view the LICENSE
+//  * file that was distributed with this source code.
+
+// spell-checker:ignore (misc) cntrl xdigit alnum punct
+
+use std::char::from_u32;
+
+/// An iterator that expands a GNU/POSIX `tr` SET specification into characters.
+///
+/// In addition to literal characters, a SET may contain ranges (`a-z`), named
+/// character classes (`[:alpha:]`, `[:digit:]`, ...), equivalence classes
+/// (`[=c=]`), repeat constructs (`[c*n]`, `[c*]`) and the usual C escape
+/// sequences (`\n`, `\t`, `\\`, octal `\ooo`, ...). Everything is expanded
+/// eagerly when the iterator is constructed so that [`next`](Iterator::next)
+/// is a cheap lookup.
+pub struct ExpandSet {
+    /// The characters the SET expands to, in order.
+    chars: Vec<char>,
+    /// Index of the next character to yield.
+    pos: usize,
+    /// When the SET ends with an open-ended repeat (`[c*]` or `[c*0]`), the
+    /// character to pad with. This is consulted by [`TranslateOperation`] to
+    /// grow SET2 to the length of SET1.
+    pub pad: Option<char>,
+}
+
+impl ExpandSet {
+    /// Parse and expand `s` into a reusable iterator over its characters.
+    pub fn new(s: &str) -> ExpandSet {
+        let mut chars = Vec::new();
+        let mut pad = None;
+        let bytes: Vec<char> = s.chars().collect();
+        let mut i = 0;
+        while i < bytes.len() {
+            // A `[` introduces a class, equivalence class or repeat; anything
+            // that does not match one of those is treated literally.
+            if bytes[i] == '[' {
+                if let Some(next) = parse_bracket(&bytes, i) {
+                    let (expanded, repeat, advance) = next;
+                    match repeat {
+                        Some(0) => pad = expanded.into_iter().next(),
+                        Some(n) => {
+                            if let Some(c) = expanded.into_iter().next() {
+                                chars.extend(std::iter::repeat(c).take(n));
+                            }
+                        }
+                        None => chars.extend(expanded),
+                    }
+                    i = advance;
+                    continue;
+                }
+            }
+
+            // Decode an escape or a plain character, then look for a range.
+            let (start, after_start) = decode_char(&bytes, i);
+            if after_start < bytes.len() && bytes[after_start] == '-' && after_start + 1 < bytes.len()
+            {
+                let (end, after_end) = decode_char(&bytes, after_start + 1);
+                match expand_range(start, end) {
+                    Some(range) => chars.extend(range),
+                    None => crash!(
+                        1,
+                        "range-endpoints of '{}-{}' are in reverse collating sequence order",
+                        start,
+                        end
+                    ),
+                }
+                i = after_end;
+            } else {
+                chars.push(start);
+                i = after_start;
+            }
+        }
+
+        ExpandSet {
+            chars,
+            pos: 0,
+            pad,
+        }
+    }
+}
+
+impl Iterator for ExpandSet {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+}
+
+/// Decode the character at `i`, honoring C escape sequences.
+///
+/// Returns the decoded character and the index of the first unconsumed
+/// character.
+fn decode_char(bytes: &[char], i: usize) -> (char, usize) {
+    if bytes[i] != '\\' || i + 1 >= bytes.len() {
+        return (bytes[i], i + 1);
+    }
+    match bytes[i + 1] {
+        '\\' => ('\\', i + 2),
+        'n' => ('\n', i + 2),
+        't' => ('\t', i + 2),
+        'r' => ('\r', i + 2),
+        'a' => ('\u{07}', i + 2),
+        'b' => ('\u{08}', i + 2),
+        'f' => ('\u{0C}', i + 2),
+        'v' => ('\u{0B}', i + 2),
+        c @ '0'..='7' => {
+            // Up to three octal digits.
+            let mut value = c.to_digit(8).unwrap();
+            let mut j = i + 2;
+            let mut count = 1;
+            while count < 3 && j < bytes.len() {
+                match bytes[j].to_digit(8) {
+                    Some(d) => {
+                        value = value * 8 + d;
+                        j += 1;
+                        count += 1;
+                    }
+                    None => break,
+                }
+            }
+            (from_u32(value).unwrap_or('\u{FFFD}'), j)
+        }
+        other => (other, i + 2),
+    }
+}
+
+/// Expand the inclusive range `start`-`end`, or `None` if it is reversed.
+fn expand_range(start: char, end: char) -> Option<Vec<char>> {
+    if (end as u32) < (start as u32) {
+        return None;
+    }
+    Some(
+        ((start as u32)..=(end as u32))
+            .filter_map(from_u32)
+            .collect(),
+    )
+}
+
+/// Try to parse a bracketed construct starting at `bytes[i] == '['`.
+///
+/// On success returns the expanded characters, an optional repeat count (for
+/// `[c*n]`), and the index past the closing bracket. Returns `None` when the
+/// bracket is just a literal `[`.
+fn parse_bracket(bytes: &[char], i: usize) -> Option<(Vec<char>, Option<usize>, usize)> {
+    // Character class: [:name:]
+    if bytes.get(i + 1) == Some(&':') {
+        let close = find(bytes, i + 2, ':', ']')?;
+        let name: String = bytes[i + 2..close].iter().collect();
+        return Some((expand_class(&name), None, close + 2));
+    }
+
+    // Equivalence class: [=c=]
+    if bytes.get(i + 1) == Some(&'=') {
+        let close = find(bytes, i + 2, '=', ']')?;
+        let inner: Vec<char> = bytes[i + 2..close].to_vec();
+        // Absent locale data, [=c=] is just the single character `c`.
+        return Some((inner, None, close + 2));
+    }
+
+    // Repeat: [c*n] or [c*]
+    // Find the matching `]`.
+    let mut j = i + 1;
+    while j < bytes.len() && bytes[j] != ']' {
+        j += 1;
+    }
+    if j >= bytes.len() {
+        return None;
+    }
+    // The content between the brackets must look like `c*...`.
+    let (c, after_c) = decode_char(bytes, i + 1);
+    if after_c < j && bytes[after_c] == '*' {
+        let digits: String = bytes[after_c + 1..j].iter().collect();
+        let count = if digits.is_empty() {
+            Some(0)
+        } else if let Some(stripped) = digits.strip_prefix('0') {
+            // Leading zero means octal.
+            if stripped.is_empty() {
+                Some(0)
+            } else {
+                usize::from_str_radix(&digits, 8).ok()
+            }
+        } else {
+            digits.parse::<usize>().ok()
+        };
+        if let Some(n) = count {
+            return Some((vec![c], Some(n), j + 1));
+        }
+    }
+
+    None
+}
+
+/// Find the index of the two-character terminator `ab` at or after `start`.
+fn find(bytes: &[char], start: usize, a: char, b: char) -> Option<usize> {
+    let mut j = start;
+    while j + 1 < bytes.len() {
+        if bytes[j] == a && bytes[j + 1] == b {
+            return Some(j);
+        }
+        j += 1;
+    }
+    None
+}
+
+/// Expand a POSIX character class name to its members in ascending order.
+fn expand_class(name: &str) -> Vec<char> {
+    let ascii = |f: fn(&char) -> bool| (0u8..=127).map(char::from).filter(|c| f(c)).collect();
+    match name {
+        "alpha" => ascii(|c| c.is_ascii_alphabetic()),
+        "digit" => ascii(|c| c.is_ascii_digit()),
+        "alnum" => ascii(|c| c.is_ascii_alphanumeric()),
+        "upper" => ascii(|c| c.is_ascii_uppercase()),
+        "lower" => ascii(|c| c.is_ascii_lowercase()),
+        "space" => vec!['\t', '\n', '\u{0B}', '\u{0C}', '\r', ' '],
+        "blank" => vec!['\t', ' '],
+        "punct" => ascii(|c| c.is_ascii_punctuation()),
+        "cntrl" => ascii(|c| c.is_ascii_control()),
+        "print" => ascii(|c| c.is_ascii_graphic() || *c == ' '),
+        "graph" => ascii(|c| c.is_ascii_graphic()),
+        "xdigit" => ascii(|c| c.is_ascii_hexdigit()),
+        _ => crash!(1, "invalid character class '{}'", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_class, expand_range, ExpandSet};
+
+    fn expand(s: &str) -> Vec<char> {
+        ExpandSet::new(s).collect()
+    }
+
+    #[test]
+    fn test_literal() {
+        assert_eq!(expand("abc"), vec!['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(expand("a-e"), vec!['a', 'b', 'c', 'd', 'e']);
+        assert_eq!(expand("0-3"), vec!['0', '1', '2', '3']);
+    }
+
+    #[test]
+    fn test_reverse_range_is_rejected() {
+        // `ExpandSet::new` would `crash!` (exit the process) on a reversed
+        // range, so exercise the underlying check directly here.
+        assert_eq!(expand_range('a', 'c'), Some(vec!['a', 'b', 'c']));
+        assert_eq!(expand_range('c', 'a'), None);
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_eq!(expand("[:digit:]"), ('0'..='9').collect::<Vec<char>>());
+        assert_eq!(expand("[:lower:]"), ('a'..='z').collect::<Vec<char>>());
+        assert_eq!(expand("[:upper:]"), ('A'..='Z').collect::<Vec<char>>());
+        assert_eq!(expand("[:blank:]"), vec!['\t', ' ']);
+        // VERTICAL TAB must be part of the space class.
+        assert!(expand_class("space").contains(&'\u{0B}'));
+        // Members are emitted in ascending order.
+        let alpha = expand("[:alpha:]");
+        assert!(alpha.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_equivalence_class() {
+        assert_eq!(expand("[=a=]"), vec!['a']);
+    }
+
+    #[test]
+    fn test_repeat_decimal_and_octal() {
+        assert_eq!(expand("[a*3]"), vec!['a', 'a', 'a']);
+        // Leading zero selects octal: 010 octal == 8.
+        assert_eq!(expand("[a*010]"), vec!['a'; 8]);
+    }
+
+    #[test]
+    fn test_repeat_pad() {
+        // An empty or zero count does not emit characters; it records a pad.
+        let set = ExpandSet::new("[a*]");
+        assert_eq!(set.pad, Some('a'));
+        assert_eq!(expand("[a*]"), Vec::<char>::new());
+    }
+
+    #[test]
+    fn test_octal_escape() {
+        // \101 octal == 65 == 'A'.
+        assert_eq!(expand("\\101"), vec!['A']);
+        assert_eq!(expand("\\t\\n"), vec!['\t', '\n']);
+    }
+}
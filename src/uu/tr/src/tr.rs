@@ -15,10 +15,8 @@ extern crate uucore;
 
 mod expand;
 
-use bit_set::BitSet;
 use clap::{App, Arg};
-use fnv::FnvHashMap;
-use std::io::{stdin, stdout, BufRead, BufWriter, Write};
+use std::io::{stdin, stdout, BufReader, BufWriter, Read, Write};
 
 use crate::expand::ExpandSet;
 use uucore::InvalidEncodingHandling;
@@ -38,28 +36,51 @@ mod options {
     pub const SETS: &str = "sets";
 }
 
+/// Build a 256-entry membership table from an expanded SET.
+fn byte_set(set: ExpandSet) -> [bool; 256] {
+    let mut table = [false; 256];
+    for c in set {
+        let value = c as u32;
+        if value < 256 {
+            table[value as usize] = true;
+        }
+    }
+    table
+}
+
+/// A transformation applied to each byte of the stream.
+///
+/// Implementors own whatever state they need (e.g. the previously seen byte
+/// for squeezing) so that the state is carried across read-buffer boundaries
+/// rather than being reset for each line.
 trait SymbolTranslator {
-    fn translate(&self, c: char, prev_c: char) -> Option<char>;
+    /// Map `c` to its replacement byte, or `None` if it should be dropped.
+    fn translate(&mut self, c: u8) -> Option<u8>;
+}
+
+impl<A: SymbolTranslator, B: SymbolTranslator> SymbolTranslator for (A, B) {
+    fn translate(&mut self, c: u8) -> Option<u8> {
+        self.0.translate(c).and_then(|c| self.1.translate(c))
+    }
 }
 
 struct DeleteOperation {
-    bset: BitSet,
+    bset: [bool; 256],
     complement: bool,
 }
 
 impl DeleteOperation {
     fn new(set: ExpandSet, complement: bool) -> DeleteOperation {
         DeleteOperation {
-            bset: set.map(|c| c as usize).collect(),
+            bset: byte_set(set),
             complement,
         }
     }
 }
 
 impl SymbolTranslator for DeleteOperation {
-    fn translate(&self, c: char, _prev_c: char) -> Option<char> {
-        let uc = c as usize;
-        if self.complement == self.bset.contains(uc) {
+    fn translate(&mut self, c: u8) -> Option<u8> {
+        if self.complement == self.bset[c as usize] {
             Some(c)
         } else {
             None
@@ -68,45 +89,57 @@ impl SymbolTranslator for DeleteOperation {
 }
 
 struct SqueezeOperation {
-    squeeze_set: BitSet,
+    squeeze_set: [bool; 256],
     complement: bool,
+    prev_c: Option<u8>,
 }
 
 impl SqueezeOperation {
     fn new(squeeze_set: ExpandSet, complement: bool) -> SqueezeOperation {
         SqueezeOperation {
-            squeeze_set: squeeze_set.map(|c| c as usize).collect(),
+            squeeze_set: byte_set(squeeze_set),
             complement,
+            prev_c: None,
         }
     }
 }
 
 impl SymbolTranslator for SqueezeOperation {
-    fn translate(&self, c: char, prev_c: char) -> Option<char> {
-        if prev_c == c && self.complement != self.squeeze_set.contains(c as usize) {
+    fn translate(&mut self, c: u8) -> Option<u8> {
+        if self.prev_c == Some(c) && self.complement != self.squeeze_set[c as usize] {
             None
         } else {
+            self.prev_c = Some(c);
             Some(c)
         }
     }
 }
 
 struct TranslateOperation {
-    translate_map: FnvHashMap<usize, char>,
+    translate_map: [u8; 256],
 }
 
 impl TranslateOperation {
     fn new(set1: ExpandSet, set2: &mut ExpandSet, truncate: bool) -> TranslateOperation {
-        let mut map = FnvHashMap::default();
-        let mut s2_prev = '_';
+        let mut map = [0u8; 256];
+        for (i, m) in map.iter_mut().enumerate() {
+            *m = i as u8;
+        }
+        let mut s2_prev = b'_';
         for i in set1 {
+            let idx = i as u32;
+            if idx >= 256 {
+                continue;
+            }
             let s2_next = set2.next();
 
             if s2_next.is_none() && truncate {
-                map.insert(i as usize, i);
+                // Leave the byte unchanged (identity already stored).
             } else {
-                s2_prev = s2_next.unwrap_or(s2_prev);
-                map.insert(i as usize, s2_prev);
+                // When SET2 is exhausted, GNU `tr` repeats its final
+                // character; an explicit `[c*]` pad overrides that choice.
+                s2_prev = s2_next.or(set2.pad).unwrap_or(s2_prev as char) as u8;
+                map[idx as usize] = s2_prev;
             }
         }
         TranslateOperation { translate_map: map }
@@ -114,8 +147,34 @@ impl TranslateOperation {
 }
 
 impl SymbolTranslator for TranslateOperation {
-    fn translate(&self, c: char, _prev_c: char) -> Option<char> {
-        Some(*self.translate_map.get(&(c as usize)).unwrap_or(&c))
+    fn translate(&mut self, c: u8) -> Option<u8> {
+        Some(self.translate_map[c as usize])
+    }
+}
+
+/// Run `translator` over every byte of `input`, writing the result to `output`.
+///
+/// Input is read in fixed-size blocks so that arbitrary binary data (including
+/// embedded NUL bytes) is handled verbatim.
+fn translate_stream<R, W, T>(mut input: R, mut output: W, mut translator: T)
+where
+    R: Read,
+    W: Write,
+    T: SymbolTranslator,
+{
+    let mut buf = [0u8; BUFFER_LEN];
+    let mut filtered = Vec::with_capacity(BUFFER_LEN);
+    while let Ok(length) = input.read(&mut buf) {
+        if length == 0 {
+            break;
+        }
+        filtered.clear();
+        for &c in &buf[..length] {
+            if let Some(t) = translator.translate(c) {
+                filtered.push(t);
+            }
+        }
+        output.write_all(&filtered).unwrap();
     }
 }
 
@@ -199,192 +258,35 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 
     let stdin = stdin();
-    let mut locked_stdin = stdin.lock();
+    let input = BufReader::new(stdin.lock());
     let stdout = stdout();
-    let locked_stdout = stdout.lock();
-    let mut buffered_stdout = BufWriter::new(locked_stdout);
+    let output = BufWriter::new(stdout.lock());
 
     let set1 = ExpandSet::new(sets[0].as_ref());
     if delete_flag {
-
-        // Define a closure that deletes characters from the input set.
         let deleter = DeleteOperation::new(set1, complement_flag);
-        let delete = |c: &char| deleter.translate(*c, 0 as char).is_some();
-
         if squeeze_flag {
-
-            // Prepare some variables to be used for the closure that
-            // computes the squeeze operation.
-            //
-            // The `squeeze()` closure needs to be defined anew for
-            // each line of input, but these variables do not change
-            // while reading the input so they can be defined before
-            // the `while` loop.
             let set2 = ExpandSet::new(sets[1].as_ref());
             let squeezer = SqueezeOperation::new(set2, complement_flag);
-
-            // Prepare some memory to read each line of the input (`buf`).
-            let mut buf = String::with_capacity(BUFFER_LEN + 4);
-
-            // Loop over each line of stdin.
-            while let Ok(length) = locked_stdin.read_line(&mut buf) {
-                if length == 0 {
-                    break;
-                }
-
-                // Define a closure that computes the squeeze operation.
-                //
-                // We keep track of the previously seen character on
-                // each call to `squeeze()`, but we need to reset the
-                // `prev_c` variable at the beginning of each line of
-                // the input. That's why we define the closure inside
-                // the `while` loop.
-                let mut prev_c = 0 as char;
-                let squeeze = |c| {
-                    let result = squeezer.translate(c, prev_c);
-                    prev_c = c;
-                    result
-                };
-
-                // Filter out the characters to delete.
-                let filtered: String = buf.chars().filter(delete).filter_map(squeeze).collect();
-                buf.clear();
-                buffered_stdout.write_all(filtered.as_bytes()).unwrap();
-            }
-
+            translate_stream(input, output, (deleter, squeezer));
         } else {
-
-            // Prepare some memory to read each line of the input (`buf`).
-            let mut buf = String::with_capacity(BUFFER_LEN + 4);
-
-            // Loop over each line of stdin.
-            while let Ok(length) = locked_stdin.read_line(&mut buf) {
-                if length == 0 {
-                    break;
-                }
-
-                // Filter out the characters to delete.
-                let filtered: String = buf.chars().filter(delete).collect();
-                buf.clear();
-                buffered_stdout.write_all(filtered.as_bytes()).unwrap();
-            }
-
+            translate_stream(input, output, deleter);
         }
     } else if squeeze_flag {
         if sets.len() < 2 {
-
-            // Prepare some variables to be used for the closure that
-            // computes the squeeze operation.
-            //
-            // The `squeeze()` closure needs to be defined anew for
-            // each line of input, but these variables do not change
-            // while reading the input so they can be defined before
-            // the `while` loop.
             let squeezer = SqueezeOperation::new(set1, complement_flag);
-
-            // Prepare some memory to read each line of the input (`buf`) and to write
-            let mut buf = String::with_capacity(BUFFER_LEN + 4);
-
-            // Loop over each line of stdin.
-            while let Ok(length) = locked_stdin.read_line(&mut buf) {
-                if length == 0 {
-                    break;
-                }
-
-                // Define a closure that computes the squeeze operation.
-                //
-                // We keep track of the previously seen character on
-                // each call to `squeeze()`, but we need to reset the
-                // `prev_c` variable at the beginning of each line of
-                // the input. That's why we define the closure inside
-                // the `while` loop.
-                let mut prev_c = 0 as char;
-                let squeeze = |c| {
-                    let result = squeezer.translate(c, prev_c);
-                    prev_c = c;
-                    result
-                };
-
-                // First translate, then squeeze each character of the input line.
-                let filtered: String = buf.chars().filter_map(squeeze).collect();
-                buf.clear();
-                buffered_stdout.write_all(filtered.as_bytes()).unwrap();
-            }
+            translate_stream(input, output, squeezer);
         } else {
-
-            // Define a closure that computes the translation using a hash map.
-            //
-            // The `unwrap()` should never panic because the
-            // `TranslateOperation.translate()` method always returns
-            // `Some`.
             let mut set2 = ExpandSet::new(sets[1].as_ref());
             let translator = TranslateOperation::new(set1, &mut set2, truncate_flag);
-            let translate = |c| translator.translate(c, 0 as char).unwrap();
-
-            // Prepare some variables to be used for the closure that
-            // computes the squeeze operation.
-            //
-            // The `squeeze()` closure needs to be defined anew for
-            // each line of input, but these variables do not change
-            // while reading the input so they can be defined before
-            // the `while` loop.
             let set2 = ExpandSet::new(sets[1].as_ref());
             let squeezer = SqueezeOperation::new(set2, complement_flag);
-
-            // Prepare some memory to read each line of the input (`buf`) and to write
-            let mut buf = String::with_capacity(BUFFER_LEN + 4);
-
-            // Loop over each line of stdin.
-            while let Ok(length) = locked_stdin.read_line(&mut buf) {
-                if length == 0 {
-                    break;
-                }
-
-                // Define a closure that computes the squeeze operation.
-                //
-                // We keep track of the previously seen character on
-                // each call to `squeeze()`, but we need to reset the
-                // `prev_c` variable at the beginning of each line of
-                // the input. That's why we define the closure inside
-                // the `while` loop.
-                let mut prev_c = 0 as char;
-                let squeeze = |c| {
-                    let result = squeezer.translate(c, prev_c);
-                    prev_c = c;
-                    result
-                };
-
-                // First translate, then squeeze each character of the input line.
-                let filtered: String = buf.chars().map(translate).filter_map(squeeze).collect();
-                buf.clear();
-                buffered_stdout.write_all(filtered.as_bytes()).unwrap();
-            }
+            translate_stream(input, output, (translator, squeezer));
         }
     } else {
-
-        // Define a closure that computes the translation using a hash map.
-        //
-        // The `unwrap()` should never panic because the
-        // `TranslateOperation.translate()` method always returns
-        // `Some`.
         let mut set2 = ExpandSet::new(sets[1].as_ref());
         let translator = TranslateOperation::new(set1, &mut set2, truncate_flag);
-        let translate = |c| translator.translate(c, 0 as char).unwrap();
-
-        // Prepare some memory to read each line of the input (`buf`) and to write
-        let mut buf = String::with_capacity(BUFFER_LEN + 4);
-
-        // Loop over each line of stdin.
-        while let Ok(length) = locked_stdin.read_line(&mut buf) {
-            if length == 0 {
-                break;
-            }
-
-            // First translate, then squeeze each character of the input line.
-            let filtered: String = buf.chars().map(translate).collect();
-            buf.clear();
-            buffered_stdout.write_all(filtered.as_bytes()).unwrap();
-        }
+        translate_stream(input, output, translator);
     }
 
     0
@@ -0,0 +1,79 @@
+//! A fixed-size ring buffer.
+//!
+//! Use the [`RingBuffer::from_iter`] function to take the last `size`
+//! elements from an iterator and store them in a ring buffer. Use
+//! [`RingBuffer::push_back`] to add an element to the buffer, possibly
+//! evicting the oldest element.
+use std::collections::VecDeque;
+
+/// A fixed-size ring buffer backed by a [`VecDeque`].
+pub struct RingBuffer<T> {
+    pub data: VecDeque<T>,
+    size: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// Create an empty ring buffer that can hold at most `size` elements.
+    pub fn new(size: usize) -> RingBuffer<T> {
+        RingBuffer {
+            data: VecDeque::new(),
+            size,
+        }
+    }
+
+    /// Create a ring buffer containing the last `size` elements of `iter`.
+    pub fn from_iter(iter: impl Iterator<Item = T>, size: usize) -> RingBuffer<T> {
+        let mut ring_buffer = RingBuffer::new(size);
+        for value in iter {
+            ring_buffer.push_back(value);
+        }
+        ring_buffer
+    }
+
+    /// Append `value`, returning the evicted element if the buffer was full.
+    ///
+    /// If `size` is zero the value is never stored and is returned immediately.
+    pub fn push_back(&mut self, value: T) -> Option<T> {
+        if self.size == 0 {
+            return Some(value);
+        }
+        let mut out = None;
+        if self.data.len() == self.size {
+            out = self.data.pop_front();
+        }
+        self.data.push_back(value);
+        out
+    }
+
+    /// The number of elements currently held in the buffer.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether the buffer currently holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The two contiguous regions of the buffer around the wrap point.
+    ///
+    /// The elements of the first slice are older than those of the second;
+    /// concatenated they yield the buffer contents in insertion order.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        self.data.as_slices()
+    }
+
+    /// Remove and discard the `count` oldest elements.
+    pub fn drain_front(&mut self, count: usize) {
+        self.data.drain(..count.min(self.data.len()));
+    }
+}
+
+impl<T: Clone> RingBuffer<T> {
+    /// Append every element of `slice`, evicting the oldest as needed.
+    pub fn extend_from_slice(&mut self, slice: &[T]) {
+        for value in slice {
+            self.push_back(value.clone());
+        }
+    }
+}
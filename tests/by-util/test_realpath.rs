@@ -107,6 +107,154 @@ fn test_realpath_file_and_links_strip_zero() {
         .stdout_contains("bar\u{0}");
 }
 
+#[test]
+fn test_canonicalize_existing_missing() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.touch("foo");
+
+    scene.ucmd().args(&["-e", "foo"]).succeeds();
+    scene.ucmd().args(&["-e", "does_not_exist"]).fails();
+}
+
+#[test]
+fn test_canonicalize_missing_allows_absent() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    let expected = format!("{}/does_not_exist\n", at.as_string());
+    scene
+        .ucmd()
+        .args(&["-m", "does_not_exist"])
+        .succeeds()
+        .stdout_is(expected);
+}
+
+#[test]
+fn test_strip_still_requires_existing() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.touch("foo");
+
+    // `-s` must not disable the `-e` existence requirement.
+    scene.ucmd().args(&["-s", "-e", "foo"]).succeeds();
+    scene.ucmd().args(&["-s", "-e", "does_not_exist"]).fails();
+}
+
+#[test]
+fn test_quiet_suppresses_errors() {
+    let scene = TestScenario::new(util_name!());
+
+    scene
+        .ucmd()
+        .args(&["-e", "-q", "does_not_exist"])
+        .fails()
+        .no_stderr();
+}
+
+#[test]
+fn test_multiple_operands() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.touch("foo");
+    at.touch("bar");
+
+    let expected = format!("{0}/foo\n{0}/bar\n", at.as_string());
+    scene
+        .ucmd()
+        .args(&["foo", "bar"])
+        .succeeds()
+        .stdout_is(expected);
+}
+
+#[test]
+fn test_multiple_operands_one_failure() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.touch("foo");
+
+    scene.ucmd().args(&["-e", "foo", "nope"]).fails();
+}
+
+#[test]
+fn test_relative_to_subdir() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir("dir1");
+    at.mkdir("dir1/dir2");
+
+    scene
+        .ucmd()
+        .args(&["--relative-to=dir1", "dir1/dir2"])
+        .succeeds()
+        .stdout_is("dir2\n");
+}
+
+#[test]
+fn test_relative_to_identical() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir("dir1");
+
+    scene
+        .ucmd()
+        .args(&["--relative-to=dir1", "dir1"])
+        .succeeds()
+        .stdout_is(".\n");
+}
+
+#[test]
+fn test_relative_to_sibling() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir("dir1");
+    at.mkdir("dir2");
+
+    scene
+        .ucmd()
+        .args(&["--relative-to=dir1", "dir2"])
+        .succeeds()
+        .stdout_is("../dir2\n");
+}
+
+#[test]
+fn test_relative_base_in_scope() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir("dir1");
+    at.mkdir("dir1/dir2");
+
+    scene
+        .ucmd()
+        .args(&["--relative-base=dir1", "--relative-to=dir1", "dir1/dir2"])
+        .succeeds()
+        .stdout_is("dir2\n");
+}
+
+#[test]
+fn test_relative_base_out_of_scope() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir("dir1");
+    at.mkdir("dir2");
+
+    let expected = format!("{}/dir2\n", at.as_string());
+    scene
+        .ucmd()
+        .args(&["--relative-base=dir1", "--relative-to=dir1", "dir2"])
+        .succeeds()
+        .stdout_is(expected);
+}
+
 #[test]
 fn test_logical() {
     let scene = TestScenario::new(util_name!());
@@ -0,0 +1,62 @@
+use crate::common::util::*;
+
+#[test]
+fn test_translate() {
+    new_ucmd!()
+        .args(&["a-z", "A-Z"])
+        .pipe_in("hello")
+        .succeeds()
+        .stdout_is("HELLO");
+}
+
+#[test]
+fn test_delete() {
+    new_ucmd!()
+        .args(&["-d", "a"])
+        .pipe_in("banana")
+        .succeeds()
+        .stdout_is("bnn");
+}
+
+#[test]
+fn test_squeeze() {
+    new_ucmd!()
+        .args(&["-s", "a"])
+        .pipe_in("aardvark")
+        .succeeds()
+        .stdout_is("ardvark");
+}
+
+#[test]
+fn test_squeeze_across_newline() {
+    // A repeated character straddling a newline must still be squeezed; the
+    // previous-character state has to survive the line boundary.
+    new_ucmd!()
+        .args(&["-s", "\n"])
+        .pipe_in("a\n\n\nb")
+        .succeeds()
+        .stdout_is("a\nb");
+}
+
+#[test]
+fn test_squeeze_across_buffer_boundary() {
+    // Feed a run far longer than the internal read block so that it spans
+    // several buffer boundaries; it must collapse to a single character.
+    let input = "a".repeat(5000);
+    new_ucmd!()
+        .args(&["-s", "a"])
+        .pipe_in(input)
+        .succeeds()
+        .stdout_is("a");
+}
+
+#[test]
+fn test_binary_passthrough() {
+    // Arbitrary bytes, including NUL, must pass through unmangled.
+    let input: Vec<u8> = vec![0x00, 0xff, b'b', 0x00, 0x80];
+    new_ucmd!()
+        .args(&["b", "c"])
+        .pipe_in(input)
+        .succeeds()
+        .stdout_is_bytes(&[0x00, 0xff, b'c', 0x00, 0x80]);
+}